@@ -0,0 +1,84 @@
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hinter, HistoryHinter};
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+/// Tab-completion and hinting for the `(deet)` prompt. At the start of a line it
+/// completes debugger command names; while completing a `break` argument it
+/// offers function names and source-file basenames pulled from the program's
+/// debug info, so breakpoints can be set on real symbols without typing them
+/// out. Previously entered lines are also offered inline as history hints.
+pub struct DeetHelper {
+    commands: Vec<String>,
+    functions: Vec<String>,
+    files: Vec<String>,
+    hinter: HistoryHinter,
+}
+
+impl DeetHelper {
+    pub fn new(functions: Vec<String>, files: Vec<String>) -> DeetHelper {
+        let commands = [
+            "run", "continue", "backtrace", "break", "watch", "step", "next", "print", "quit",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        DeetHelper { commands, functions, files, hinter: HistoryHinter {} }
+    }
+}
+
+impl Completer for DeetHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> Result<(usize, Vec<Pair>), ReadlineError> {
+        let prefix = &line[..pos];
+        let pair = |c: &String| Pair { display: c.clone(), replacement: c.clone() };
+
+        // Still typing the command word: complete against command names.
+        if !prefix.contains(char::is_whitespace) {
+            let candidates = self
+                .commands
+                .iter()
+                .filter(|c| c.starts_with(prefix))
+                .map(pair)
+                .collect();
+            return Ok((0, candidates));
+        }
+
+        // Completing a `break` argument: offer function names and file basenames.
+        let command = prefix.split_whitespace().next().unwrap_or("");
+        if matches!(command, "b" | "break") {
+            let arg = prefix.rsplit(char::is_whitespace).next().unwrap_or("");
+            let start = pos - arg.len();
+            let candidates = self
+                .functions
+                .iter()
+                .chain(self.files.iter())
+                .filter(|c| c.starts_with(arg))
+                .map(pair)
+                .collect();
+            return Ok((start, candidates));
+        }
+
+        Ok((pos, Vec::new()))
+    }
+}
+
+impl Hinter for DeetHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
+        self.hinter.hint(line, pos, ctx)
+    }
+}
+
+impl Highlighter for DeetHelper {}
+impl Validator for DeetHelper {}
+impl Helper for DeetHelper {}
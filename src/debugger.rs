@@ -4,11 +4,12 @@ use rustyline::error::ReadlineError;
 use rustyline::Editor;
 use rustyline::history::FileHistory;
 use crate::dwarf_data::{DwarfData, Error as DwarfError};
+use crate::completer::DeetHelper;
 
 pub struct Debugger {
     target: String,
     history_path: String,
-    readline: Editor<(), FileHistory>,
+    readline: Editor<DeetHelper, FileHistory>,
     inferior: Option<Inferior>,
     debug_data: DwarfData,
     break_points: Vec<usize>,
@@ -30,7 +31,11 @@ impl Debugger {
         };
 
         let history_path = format!("{}/.deet_history", std::env::var("HOME").unwrap());
-        let mut readline = Editor::<(), FileHistory>::new().expect("Create editor fails.");
+        let mut readline = Editor::<DeetHelper, FileHistory>::new().expect("Create editor fails.");
+        readline.set_helper(Some(DeetHelper::new(
+            debug_data.all_function_names(),
+            debug_data.file_basenames(),
+        )));
         // Attempt to load history from ~/.deet_history if it exists
         let _ = readline.load_history(&history_path);
 
@@ -49,16 +54,29 @@ impl Debugger {
 
     fn print_status(&self, status: Option<Status>) -> Option<Status> {
         match status {
-            Some(Status::Exited(code)) => { println!("Child exited (status {})", code); return status; },
+            Some(Status::Exited(code)) => { println!("Child exited (status {})", code); status },
             Some(Status::Stopped(sig, rip)) => {
                 println!("Child stopped (signal {})", sig);
                 if let Some(line) = self.debug_data.get_line_from_addr(rip) {
                     println!("Stopped at {}", line);
                 }
-                return status;
+                status
             }
+            Some(Status::Signaled(sig)) => { println!("Child signaled (signal {})", sig); status }
             None => { println!("continue fails!"); None }
-            _ => { None }     // other cases
+        }
+    }
+
+    /// After the inferior stops, reports every hardware watchpoint that
+    /// fired, printing its index and the old/new values of the watched
+    /// location.
+    fn report_watchpoint(&mut self) {
+        if let Some(inferior) = self.inferior.as_mut() {
+            if let Ok(hits) = inferior.check_watchpoints() {
+                for (idx, old, new) in hits {
+                    println!("Watchpoint {} hit: {:#x} -> {:#x}", idx, old, new);
+                }
+            }
         }
     }
 
@@ -66,7 +84,7 @@ impl Debugger {
         let addr_without_0x = if addr.to_lowercase().starts_with("0x") {
             &addr[2..]
         } else {
-            &addr
+            addr
         };
         usize::from_str_radix(addr_without_0x, 16).ok()
     }
@@ -74,16 +92,18 @@ impl Debugger {
     pub fn run(&mut self) {
         loop {
             match self.get_next_command() {
-                DebuggerCommand::Run(args) => {
-                    if let Some(inferior) = Inferior::new(&self.target, &args, &self.break_points) {
-                        if self.inferior.is_some() {
-                            self.inferior.as_mut().unwrap().kill().ok();
+                DebuggerCommand::Run(config) => {
+                    if let Some(inferior) = Inferior::new(&self.target, &config, &self.break_points) {
+                        if let Some(old) = self.inferior.as_mut() {
+                            old.kill().ok();
                         }
                         // Create the inferior
                         self.inferior = Some(inferior);
                         let status = self.inferior.as_mut().unwrap().go().ok();
                         if let Some(Status::Exited(_)) = self.print_status(status) {
                             self.inferior = None;
+                        } else {
+                            self.report_watchpoint();
                         }
                     } else {
                         println!("Error starting subprocess");
@@ -97,6 +117,8 @@ impl Debugger {
                     let status = self.inferior.as_mut().unwrap().go().ok();
                     if let Some(Status::Exited(_)) = self.print_status(status) {
                         self.inferior = None;
+                    } else {
+                        self.report_watchpoint();
                     }
                 },
                 DebuggerCommand::Backtrace => {
@@ -107,8 +129,8 @@ impl Debugger {
                     self.inferior.as_ref().unwrap().print_backtrace(&self.debug_data).expect("");
                 },
                 DebuggerCommand::Breakpoint(target) => {
-                    if target.starts_with('*') {
-                        if let Some(addr) = Debugger::parse_address(&target[1..]) {
+                    if let Some(raw) = target.strip_prefix('*') {
+                        if let Some(addr) = Debugger::parse_address(raw) {
                             self.break_points.push(addr);
                             println!("Set breakpoint {} at {:#x}", self.break_points.len()-1, addr);
                         }
@@ -120,20 +142,82 @@ impl Debugger {
                             println!("Set breakpoint {} at {:#x}", self.break_points.len()-1, addr);
                         }
                     }
-                    else if let Some(function) = self.debug_data.find_function(target) {
+                    else if let Some(function) = self.debug_data.find_function(&target) {
                         if let Some(addr) = self.debug_data.get_addr_for_function(None, &function) {
                             self.break_points.push(addr);
                             println!("Set breakpoint {} at {:#x}", self.break_points.len()-1, addr);
                         }
                     }
                     else { println!("Invalid breakpoint target."); }
-                    if self.inferior.is_some() {
-                        self.inferior.as_mut().unwrap().install_breakpoints(&self.break_points);
+                    if let Some(inferior) = self.inferior.as_mut() {
+                        inferior.install_breakpoints(&self.break_points);
+                    }
+                }
+                DebuggerCommand::Print(expr) => {
+                    if self.inferior.is_none() {
+                        println!("The program is not being run.");
+                        continue;
+                    }
+                    let inferior = self.inferior.as_ref().unwrap();
+                    match inferior.frame() {
+                        Ok((rbp, rip)) => {
+                            match crate::syntax::evaluate(&expr, inferior, &self.debug_data, rbp, rip) {
+                                Ok(value) => println!("{} = {}", expr, value),
+                                Err(err) => println!("{}", err),
+                            }
+                        }
+                        Err(err) => println!("Could not read registers: {}", err),
+                    }
+                }
+                DebuggerCommand::Step => {
+                    if self.inferior.is_none() {
+                        println!("The program is not being run.");
+                        continue;
+                    }
+                    let status = self.inferior.as_ref().unwrap().step_line(&self.debug_data).ok();
+                    if let Some(Status::Exited(_)) = self.print_status(status) {
+                        self.inferior = None;
+                    }
+                }
+                DebuggerCommand::Next => {
+                    if self.inferior.is_none() {
+                        println!("The program is not being run.");
+                        continue;
+                    }
+                    let status = self.inferior.as_ref().unwrap().next_line(&self.debug_data).ok();
+                    if let Some(Status::Exited(_)) = self.print_status(status) {
+                        self.inferior = None;
+                    }
+                }
+                DebuggerCommand::Watch(target) => {
+                    if self.inferior.is_none() {
+                        println!("The program is not being run.");
+                        continue;
+                    }
+                    // A raw `*addr` target carries no DWARF type, so there's no
+                    // variable size to derive a watch length from; fall back to
+                    // a full word. A named variable's watch length comes from
+                    // its actual type width instead of always spanning 8 bytes,
+                    // so e.g. a 4-byte `int` arms a correctly aligned watchpoint
+                    // rather than one that silently never traps.
+                    let addr_and_size = if let Some(raw) = target.strip_prefix('*') {
+                        Debugger::parse_address(raw).map(|addr| (addr, std::mem::size_of::<usize>()))
+                    } else {
+                        self.debug_data.get_addr_for_variable(None, &target)
+                    };
+                    match addr_and_size {
+                        Some((addr, size)) => {
+                            match self.inferior.as_mut().unwrap().set_watchpoint(addr, size) {
+                                Some(idx) => println!("Set watchpoint {} at {:#x}", idx, addr),
+                                None => println!("Could not set watchpoint (all four hardware watchpoints in use, or debug registers inaccessible)."),
+                            }
+                        }
+                        None => println!("Invalid watchpoint target."),
                     }
                 }
                 DebuggerCommand::Quit => {
-                    if self.inferior.is_some() {
-                        self.inferior.as_mut().unwrap().kill().ok();
+                    if let Some(inferior) = self.inferior.as_mut() {
+                        inferior.kill().ok();
                     }
                     return;
                 }
@@ -161,7 +245,7 @@ impl Debugger {
                     panic!("Unexpected I/O error: {:?}", err);
                 }
                 Ok(line) => {
-                    if line.trim().len() == 0 {
+                    if line.trim().is_empty() {
                         continue;
                     }
                     let _ = self.readline.add_history_entry(line.as_str());
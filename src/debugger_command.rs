@@ -0,0 +1,94 @@
+use crate::inferior::RunConfig;
+
+pub enum DebuggerCommand {
+    Quit,
+    Run(RunConfig),
+    Continue,
+    Backtrace,
+    Breakpoint(String),
+    Watch(String),
+    Step,
+    Next,
+    Print(String),
+}
+
+impl DebuggerCommand {
+    pub fn from_tokens(tokens: &[&str]) -> Option<DebuggerCommand> {
+        match tokens[0] {
+            "q" | "quit" => Some(DebuggerCommand::Quit),
+            "r" | "run" => Some(DebuggerCommand::Run(parse_run_config(&tokens[1..]))),
+            "c" | "cont" | "continue" => Some(DebuggerCommand::Continue),
+            "bt" | "back" | "backtrace" => Some(DebuggerCommand::Backtrace),
+            "b" | "break" => {
+                if tokens.len() < 2 {
+                    return None;
+                }
+                Some(DebuggerCommand::Breakpoint(tokens[1].to_string()))
+            }
+            "p" | "print" => {
+                if tokens.len() < 2 {
+                    return None;
+                }
+                Some(DebuggerCommand::Print(tokens[1..].join(" ")))
+            }
+            "s" | "step" => Some(DebuggerCommand::Step),
+            "n" | "next" => Some(DebuggerCommand::Next),
+            "w" | "watch" => {
+                if tokens.len() < 2 {
+                    return None;
+                }
+                Some(DebuggerCommand::Watch(tokens[1].to_string()))
+            }
+            // Default case:
+            _ => None,
+        }
+    }
+}
+
+/// Parses the tokens following `run` into a [`RunConfig`]. Recognises stream
+/// redirections (`< infile`, `> outfile`, `2> errfile`, with or without a space
+/// before the path) and leading `VAR=value` environment assignments; everything
+/// else is treated as a positional argument.
+fn parse_run_config(tokens: &[&str]) -> RunConfig {
+    let mut config = RunConfig::default();
+    let mut iter = tokens.iter().peekable();
+    // Environment assignments are only honoured as a prefix, before the first
+    // positional argument, matching the shell's `VAR=value cmd` convention.
+    let mut in_prefix = true;
+    while let Some(&token) = iter.next() {
+        let next_path = |iter: &mut std::iter::Peekable<std::slice::Iter<&str>>, rest: &str| {
+            if rest.is_empty() {
+                iter.next().map(|s| s.to_string())
+            } else {
+                Some(rest.to_string())
+            }
+        };
+        if let Some(rest) = token.strip_prefix("2>") {
+            config.stderr = next_path(&mut iter, rest);
+        } else if let Some(rest) = token.strip_prefix('>') {
+            config.stdout = next_path(&mut iter, rest);
+        } else if let Some(rest) = token.strip_prefix('<') {
+            config.stdin = next_path(&mut iter, rest);
+        } else if in_prefix && is_env_assignment(token) {
+            let (name, value) = token.split_once('=').unwrap();
+            config.env.push((name.to_string(), value.to_string()));
+        } else {
+            in_prefix = false;
+            config.args.push(token.to_string());
+        }
+    }
+    config
+}
+
+/// Returns true if `token` looks like a `VAR=value` assignment, i.e. a valid
+/// identifier followed by `=`.
+fn is_env_assignment(token: &str) -> bool {
+    match token.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,454 @@
+//! Parses the subset of DWARF debug info `deet` needs: source line tables,
+//! function address ranges, and the type/variable layout used by `print` and
+//! `watch`. Built once from the target binary in [`DwarfData::from_file`] and
+//! queried by address or name for the rest of the program's life.
+
+use crate::gimli_wrapper::{self, Die, Dwarf, Unit};
+use gimli::Reader;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    ErrorOpeningFile,
+    DwarfFormatError(gimli_wrapper::Error),
+}
+
+impl From<gimli_wrapper::Error> for Error {
+    fn from(err: gimli_wrapper::Error) -> Error {
+        Error::DwarfFormatError(err)
+    }
+}
+
+impl From<gimli::Error> for Error {
+    fn from(err: gimli::Error) -> Error {
+        Error::DwarfFormatError(gimli_wrapper::Error::Gimli(err))
+    }
+}
+
+/// A resolved source location: the file it came from, its line number, and
+/// the lowest instruction address attributed to it.
+pub struct Line {
+    pub address: usize,
+    pub file: String,
+    pub number: usize,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.number)
+    }
+}
+
+/// A DWARF type, resolved to exactly the shape [`crate::syntax`] needs to walk
+/// `.field` / `[i]` / `*` and to format a value's final bytes. Qualifiers
+/// (`typedef`, `const`, `volatile`) are stripped away during resolution since
+/// `print` doesn't care about them.
+#[derive(Clone)]
+pub enum Type {
+    /// A scalar such as `int` or `char`. `name` isn't read anywhere: `print`'s
+    /// formatting only cares about `size`/`signed`.
+    #[allow(dead_code)]
+    Base { name: String, size: usize, signed: bool },
+    /// A pointer to another type.
+    Pointer { target: Box<Type> },
+    /// A `struct`, with its members in declaration order.
+    Struct { name: String, size: usize, members: Vec<Member> },
+    /// A fixed-size array.
+    Array { element: Box<Type>, stride: usize, count: usize },
+}
+
+/// One field of a [`Type::Struct`].
+#[derive(Clone)]
+pub struct Member {
+    pub name: String,
+    pub offset: usize,
+    pub member_type: Type,
+}
+
+/// A local variable or parameter: its offset from the enclosing frame's
+/// base (`DW_OP_fbreg`, relative to the CFA -- see [`frame_offset`]) and its
+/// type.
+pub struct Variable {
+    pub offset: isize,
+    pub var_type: Type,
+}
+
+/// Size in bytes that a value of `ty` occupies in the inferior's memory.
+/// Used to size hardware watchpoints and to compute array strides.
+pub fn type_size(ty: &Type) -> usize {
+    match ty {
+        Type::Base { size, .. } => *size,
+        Type::Pointer { .. } => 8,
+        Type::Struct { size, .. } => *size,
+        Type::Array { stride, count, .. } => stride * count,
+    }
+}
+
+struct Function {
+    name: String,
+    low_pc: usize,
+    high_pc: usize,
+    unit_index: usize,
+    offset: gimli::UnitOffset,
+}
+
+pub struct DwarfData {
+    dwarf: Dwarf,
+    units: Vec<Unit>,
+    functions: Vec<Function>,
+    lines: Vec<Line>,
+    files: Vec<String>,
+    globals: Vec<(String, usize, Type)>,
+}
+
+impl DwarfData {
+    /// Parses `path`'s DWARF sections, collecting everything needed to
+    /// answer the lookups below up front.
+    pub fn from_file(path: &str) -> Result<DwarfData, Error> {
+        let dwarf = gimli_wrapper::load(path).map_err(|e| match e {
+            gimli_wrapper::Error::Io => Error::ErrorOpeningFile,
+            other => Error::DwarfFormatError(other),
+        })?;
+
+        let mut units = Vec::new();
+        let mut functions = Vec::new();
+        let mut lines = Vec::new();
+        let mut files: Vec<String> = Vec::new();
+        let mut globals = Vec::new();
+
+        let mut unit_iter = dwarf.units();
+        while let Some(header) = unit_iter.next()? {
+            let unit = dwarf.unit(header)?;
+            let unit_index = units.len();
+
+            if let Some(program) = unit.line_program.clone() {
+                let mut rows = program.rows();
+                while let Some((header, row)) = rows.next_row()? {
+                    if let (Some(file_entry), Some(line)) = (row.file(header), row.line()) {
+                        if let Some(path) = gimli_wrapper::file_path(&dwarf, &unit, header, file_entry) {
+                            if !files.contains(&path) {
+                                files.push(path.clone());
+                            }
+                            lines.push(Line { address: row.address() as usize, file: path, number: line.get() as usize });
+                        }
+                    }
+                }
+            }
+
+            let mut entries = unit.entries();
+            let mut depth = 0isize;
+            while let Some((delta, entry)) = entries.next_dfs()? {
+                depth += delta;
+                match entry.tag() {
+                    gimli::DW_TAG_subprogram => {
+                        if let (Some(name), Some(low_pc)) = (gimli_wrapper::die_name(&dwarf, &unit, entry), low_pc(entry)) {
+                            let high_pc = high_pc(entry, low_pc);
+                            functions.push(Function {
+                                name,
+                                low_pc: low_pc as usize,
+                                high_pc: high_pc as usize,
+                                unit_index,
+                                offset: entry.offset(),
+                            });
+                        }
+                    }
+                    gimli::DW_TAG_variable if depth == 1 => {
+                        if let Some(name) = gimli_wrapper::die_name(&dwarf, &unit, entry) {
+                            if let (Some(addr), Some(var_type)) = (global_addr(entry), resolve_type(&dwarf, &unit, entry)) {
+                                globals.push((name, addr as usize, var_type));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            units.push(unit);
+        }
+
+        lines.sort_by_key(|l| l.address);
+        functions.sort_by_key(|f| f.low_pc);
+
+        Ok(DwarfData { dwarf, units, functions, lines, files, globals })
+    }
+
+    /// Prints a summary of the parsed debug info (used by the `-i` flag).
+    pub fn print(&self) {
+        println!("Functions:");
+        for f in &self.functions {
+            println!("  {} ({:#x}-{:#x})", f.name, f.low_pc, f.high_pc);
+        }
+        println!("Files:");
+        for file in &self.files {
+            println!("  {}", file);
+        }
+    }
+
+    /// All known function names, for tab completion.
+    pub fn all_function_names(&self) -> Vec<String> {
+        self.functions.iter().map(|f| f.name.clone()).collect()
+    }
+
+    /// All known source file basenames, for tab completion.
+    pub fn file_basenames(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .filter_map(|f| std::path::Path::new(f).file_name())
+            .map(|s| s.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Returns the name of the function whose address range contains `addr`.
+    pub fn get_function_from_addr(&self, addr: usize) -> Option<String> {
+        self.functions.iter().find(|f| addr >= f.low_pc && addr < f.high_pc).map(|f| f.name.clone())
+    }
+
+    /// Returns `name` itself if a function by that name exists.
+    pub fn find_function(&self, name: &str) -> Option<String> {
+        self.functions.iter().find(|f| f.name == name).map(|f| f.name.clone())
+    }
+
+    /// Returns the address to set a breakpoint at for a call to `name`,
+    /// skipping the prologue by picking the first line-table row inside the
+    /// function after its entry point.
+    pub fn get_addr_for_function(&self, _file: Option<&str>, name: &str) -> Option<usize> {
+        let f = self.functions.iter().find(|f| f.name == name)?;
+        let after_prologue = self
+            .lines
+            .iter()
+            .filter(|l| l.address > f.low_pc && l.address < f.high_pc)
+            .map(|l| l.address)
+            .min();
+        Some(after_prologue.unwrap_or(f.low_pc))
+    }
+
+    /// Returns the address of the first instruction attributed to
+    /// `line_number`, optionally restricted to a file whose path ends with
+    /// `file`.
+    pub fn get_addr_for_line(&self, file: Option<&str>, line_number: usize) -> Option<usize> {
+        self.lines
+            .iter()
+            .filter(|l| l.number == line_number && file.is_none_or(|file| l.file.ends_with(file)))
+            .map(|l| l.address)
+            .min()
+    }
+
+    /// Returns the source line attributed to `addr` (the line-table row with
+    /// the greatest address not exceeding it).
+    pub fn get_line_from_addr(&self, addr: usize) -> Option<Line> {
+        self.lines
+            .iter()
+            .filter(|l| l.address <= addr)
+            .max_by_key(|l| l.address)
+            .map(|l| Line { address: l.address, file: l.file.clone(), number: l.number })
+    }
+
+    /// Looks up a global variable's address and the byte size of its type,
+    /// used to arm a hardware watchpoint over it.
+    pub fn get_addr_for_variable(&self, _frame: Option<&str>, name: &str) -> Option<(usize, usize)> {
+        self.globals
+            .iter()
+            .find(|(n, _, _)| n == name)
+            .map(|(_, addr, var_type)| (*addr, type_size(var_type)))
+    }
+
+    /// Looks up a local variable or parameter declared in `func_name`,
+    /// returning its frame offset and type.
+    pub fn get_variable_in_function(&self, func_name: &str, var_name: &str) -> Option<Variable> {
+        let f = self.functions.iter().find(|f| f.name == func_name)?;
+        let unit = &self.units[f.unit_index];
+        let mut cursor = unit.entries_at_offset(f.offset).ok()?;
+        // The first `next_dfs()` off `entries_at_offset` yields the anchor
+        // entry itself (the subprogram DIE, delta 0), not a child; skip it so
+        // `depth` only tracks descent into its actual children.
+        cursor.next_dfs().ok()??;
+        let mut depth = 0isize;
+        while let Some((delta, entry)) = cursor.next_dfs().ok()? {
+            depth += delta;
+            if depth <= 0 {
+                break;
+            }
+            if matches!(entry.tag(), gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable)
+                && gimli_wrapper::die_name(&self.dwarf, unit, entry).as_deref() == Some(var_name)
+            {
+                let offset = frame_offset(entry)?;
+                let var_type = resolve_type(&self.dwarf, unit, entry)?;
+                return Some(Variable { offset, var_type });
+            }
+        }
+        None
+    }
+}
+
+fn low_pc(entry: &Die) -> Option<u64> {
+    match entry.attr_value(gimli::DW_AT_low_pc).ok()?? {
+        gimli::AttributeValue::Addr(addr) => Some(addr),
+        _ => None,
+    }
+}
+
+fn high_pc(entry: &Die, low_pc: u64) -> u64 {
+    match entry.attr_value(gimli::DW_AT_high_pc).ok().flatten() {
+        Some(gimli::AttributeValue::Addr(addr)) => addr,
+        Some(gimli::AttributeValue::Udata(offset)) => low_pc + offset,
+        _ => low_pc,
+    }
+}
+
+fn byte_size(entry: &Die) -> Option<u64> {
+    entry.attr_value(gimli::DW_AT_byte_size).ok()?.and_then(|v| v.udata_value())
+}
+
+fn encoding_is_signed(entry: &Die) -> bool {
+    let encoding = entry
+        .attr_value(gimli::DW_AT_encoding)
+        .ok()
+        .flatten()
+        .and_then(|v| v.udata_value())
+        .unwrap_or(0);
+    matches!(encoding as u8, e if gimli::DwAte(e) == gimli::DW_ATE_signed || gimli::DwAte(e) == gimli::DW_ATE_signed_char)
+}
+
+fn member_offset(entry: &Die) -> Option<u64> {
+    entry.attr_value(gimli::DW_AT_data_member_location).ok()?.and_then(|v| v.udata_value())
+}
+
+fn subrange_count(entry: &Die) -> Option<usize> {
+    if let Some(count) = entry.attr_value(gimli::DW_AT_count).ok().flatten().and_then(|v| v.udata_value()) {
+        return Some(count as usize);
+    }
+    let upper_bound = entry.attr_value(gimli::DW_AT_upper_bound).ok().flatten().and_then(|v| v.udata_value())?;
+    Some(upper_bound as usize + 1)
+}
+
+/// Decodes the `DW_OP_fbreg <offset>` expression DWARF emits for locals and
+/// parameters kept in the stack frame. We don't support any richer location
+/// expression, which matches `print`'s assumption that every variable lives
+/// at a fixed offset from the frame base (`DW_AT_frame_base`'s
+/// `DW_OP_call_frame_cfa`, which for a standard `push rbp; mov rbp, rsp`
+/// prologue is `rbp + 16`).
+fn frame_offset(entry: &Die) -> Option<isize> {
+    let attr = entry.attr_value(gimli::DW_AT_location).ok()??;
+    let expr = match attr {
+        gimli::AttributeValue::Exprloc(expr) => expr,
+        _ => return None,
+    };
+    let bytes = expr.0.to_slice().ok()?;
+    if bytes.first() != Some(&gimli::DW_OP_fbreg.0) {
+        return None;
+    }
+    read_sleb128(&bytes[1..])
+}
+
+/// Decodes the `DW_OP_addr <address>` expression DWARF emits for the
+/// location of a global variable.
+fn global_addr(entry: &Die) -> Option<u64> {
+    let attr = entry.attr_value(gimli::DW_AT_location).ok()??;
+    let expr = match attr {
+        gimli::AttributeValue::Exprloc(expr) => expr,
+        _ => return None,
+    };
+    let bytes = expr.0.to_slice().ok()?;
+    if bytes.first() != Some(&gimli::DW_OP_addr.0) || bytes.len() < 9 {
+        return None;
+    }
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[1..9]);
+    Some(u64::from_le_bytes(buf))
+}
+
+fn read_sleb128(bytes: &[u8]) -> Option<isize> {
+    let mut result: i64 = 0;
+    let mut shift = 0;
+    let mut idx = 0;
+    loop {
+        let byte = *bytes.get(idx)?;
+        idx += 1;
+        result |= ((byte & 0x7f) as i64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                result |= -1i64 << shift;
+            }
+            return Some(result as isize);
+        }
+    }
+}
+
+/// Resolves the type referenced by `entry`'s `DW_AT_type` attribute.
+fn resolve_type(dwarf: &Dwarf, unit: &Unit, entry: &Die) -> Option<Type> {
+    let offset = match entry.attr_value(gimli::DW_AT_type).ok()?? {
+        gimli::AttributeValue::UnitRef(offset) => offset,
+        _ => return None,
+    };
+    let type_die = unit.entry(offset).ok()?;
+    resolve_type_die(dwarf, unit, &type_die)
+}
+
+fn resolve_type_die(dwarf: &Dwarf, unit: &Unit, die: &Die) -> Option<Type> {
+    match die.tag() {
+        gimli::DW_TAG_base_type => {
+            let name = gimli_wrapper::die_name(dwarf, unit, die).unwrap_or_else(|| "?".to_string());
+            let size = byte_size(die).unwrap_or(8) as usize;
+            Some(Type::Base { name, size, signed: encoding_is_signed(die) })
+        }
+        gimli::DW_TAG_pointer_type => {
+            let target = resolve_type(dwarf, unit, die)
+                .unwrap_or(Type::Base { name: "void".to_string(), size: 1, signed: false });
+            Some(Type::Pointer { target: Box::new(target) })
+        }
+        gimli::DW_TAG_structure_type => {
+            let name = gimli_wrapper::die_name(dwarf, unit, die).unwrap_or_else(|| "struct".to_string());
+            let size = byte_size(die).unwrap_or(0) as usize;
+            let mut members = Vec::new();
+            let mut cursor = unit.entries_at_offset(die.offset()).ok()?;
+            // Skip the anchor entry (the struct DIE itself) the first
+            // `next_dfs()` yields, so `depth` tracks descent into its members.
+            cursor.next_dfs().ok()??;
+            let mut depth = 0isize;
+            while let Some((delta, child)) = cursor.next_dfs().ok()? {
+                depth += delta;
+                if depth <= 0 {
+                    break;
+                }
+                if depth == 1 && child.tag() == gimli::DW_TAG_member {
+                    if let (Some(member_name), Some(member_type)) =
+                        (gimli_wrapper::die_name(dwarf, unit, child), resolve_type(dwarf, unit, child))
+                    {
+                        members.push(Member {
+                            name: member_name,
+                            offset: member_offset(child).unwrap_or(0) as usize,
+                            member_type,
+                        });
+                    }
+                }
+            }
+            Some(Type::Struct { name, size, members })
+        }
+        gimli::DW_TAG_array_type => {
+            let element = resolve_type(dwarf, unit, die)?;
+            let mut count = 0usize;
+            let mut cursor = unit.entries_at_offset(die.offset()).ok()?;
+            // Skip the anchor entry (the array DIE itself) the first
+            // `next_dfs()` yields, so `depth` tracks descent into its subrange.
+            cursor.next_dfs().ok()??;
+            let mut depth = 0isize;
+            while let Some((delta, child)) = cursor.next_dfs().ok()? {
+                depth += delta;
+                if depth <= 0 {
+                    break;
+                }
+                if depth == 1 && child.tag() == gimli::DW_TAG_subrange_type {
+                    count = subrange_count(child).unwrap_or(0);
+                    break;
+                }
+            }
+            let stride = type_size(&element);
+            Some(Type::Array { element: Box::new(element), stride, count })
+        }
+        gimli::DW_TAG_typedef | gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type => {
+            resolve_type(dwarf, unit, die)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,78 @@
+//! Thin loader that turns an on-disk ELF binary into a `gimli::Dwarf`.
+//!
+//! The debugger loads exactly one target binary for the life of the process,
+//! so rather than thread a borrow of the file's bytes through `DwarfData` we
+//! just leak them to get a `'static` slice gimli can hold onto directly.
+
+use gimli::{EndianSlice, RunTimeEndian};
+use object::{Object, ObjectSection};
+use std::borrow::Cow;
+
+/// The `gimli::Reader` implementation used throughout `dwarf_data`.
+pub type Slice = EndianSlice<'static, RunTimeEndian>;
+pub type Dwarf = gimli::Dwarf<Slice>;
+pub type Unit = gimli::Unit<Slice>;
+pub type Die<'a> = gimli::DebuggingInformationEntry<'a, 'a, Slice>;
+
+// The inner errors are never pattern-matched on, only ever surfaced through
+// the derived `Debug` impl when `debugger.rs` prints `DwarfFormatError`.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum Error {
+    Io,
+    Object(object::Error),
+    Gimli(gimli::Error),
+}
+
+impl From<gimli::Error> for Error {
+    fn from(err: gimli::Error) -> Error {
+        Error::Gimli(err)
+    }
+}
+
+/// Reads `path` and parses the DWARF sections gimli understands.
+pub fn load(path: &str) -> Result<Dwarf, Error> {
+    let data = std::fs::read(path).map_err(|_| Error::Io)?;
+    let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+    let object = object::File::parse(data).map_err(Error::Object)?;
+    let endian = if object.is_little_endian() { RunTimeEndian::Little } else { RunTimeEndian::Big };
+
+    let load_section = |id: gimli::SectionId| -> Result<Slice, Error> {
+        let data = match object.section_by_name(id.name()) {
+            Some(section) => section.uncompressed_data().map_err(Error::Object)?,
+            None => Cow::Borrowed(&[][..]),
+        };
+        let data: &'static [u8] = match data {
+            Cow::Borrowed(d) => d,
+            Cow::Owned(d) => Box::leak(d.into_boxed_slice()),
+        };
+        Ok(EndianSlice::new(data, endian))
+    };
+
+    gimli::Dwarf::load(load_section)
+}
+
+/// Reads the `DW_AT_name` of `die`, resolving it through the `.debug_str`
+/// section if needed.
+pub fn die_name(dwarf: &Dwarf, unit: &Unit, die: &Die) -> Option<String> {
+    let attr = die.attr_value(gimli::DW_AT_name).ok()??;
+    Some(dwarf.attr_string(unit, attr).ok()?.to_string_lossy().into_owned())
+}
+
+/// Reads a file's full path (directory, if any, joined with its name) out of
+/// the line program header for `unit`.
+pub fn file_path(dwarf: &Dwarf, unit: &Unit, header: &gimli::LineProgramHeader<Slice>, file: &gimli::FileEntry<Slice>) -> Option<String> {
+    let mut path = String::new();
+    if let Some(dir) = file.directory(header) {
+        if let Ok(dir) = dwarf.attr_string(unit, dir) {
+            let dir = dir.to_string_lossy();
+            if !dir.is_empty() {
+                path.push_str(&dir);
+                path.push('/');
+            }
+        }
+    }
+    let name = dwarf.attr_string(unit, file.path_name()).ok()?;
+    path.push_str(&name.to_string_lossy());
+    Some(path)
+}
@@ -2,13 +2,27 @@ use std::collections::HashMap;
 use std::mem::size_of;
 use std::os::unix::process::CommandExt;
 use nix::sys::ptrace;
-use nix::sys::signal;
 use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 use nix::unistd::Pid;
 use std::process::{Child, Command};
+use std::fs::File;
 use nix::sys::signal::Signal;
 use crate::dwarf_data::{DwarfData, Line};
 
+/// How to launch the inferior: its argument vector, extra environment
+/// variables, and optional file redirections for the three standard streams.
+/// Mirrors the `std::process::Command` model (an env map plus explicit
+/// stdin/stdout/stderr), keeping redirection isolated to the child just as
+/// ptrace `TRACEME` already is.
+#[derive(Default)]
+pub struct RunConfig {
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub stdin: Option<String>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+}
+
 pub enum Status {
     /// Indicates inferior stopped. Contains the signal that stopped the process, as well as the
     /// current instruction pointer that it is stopped at.
@@ -25,29 +39,57 @@ pub enum Status {
 /// This function calls ptrace with PTRACE_TRACEME to enable debugging on a process. You should use
 /// pre_exec with Command to call this in the child process.
 fn child_traceme() -> Result<(), std::io::Error> {
-    ptrace::traceme().or(Err(std::io::Error::new(
-        std::io::ErrorKind::Other,
-        "ptrace TRACEME failed",
-    )))
+    ptrace::traceme().or(Err(std::io::Error::other("ptrace TRACEME failed")))
 }
 
 pub struct Inferior {
     child: Child,
     breakpoint_map: HashMap<usize, u8>,
+    watchpoints: Vec<Watchpoint>,
+}
+
+/// A single active hardware data watchpoint, occupying one of the four x86-64
+/// debug-address registers DR0–DR3. `value` caches the last observed contents
+/// so a hit can report the old value alongside the new one.
+struct Watchpoint {
+    addr: usize,
+    size: usize,
+    value: u64,
 }
 
+/// Number of x86-64 hardware debug-address registers (DR0–DR3).
+const DR_COUNT: usize = 4;
+
+/// Byte offset of `u_debugreg[0]` within `struct user` on x86-64. The debug
+/// registers are reached through `PTRACE_POKEUSER`/`PTRACE_PEEKUSER` at
+/// `DEBUGREG_OFFSET + n * size_of::<usize>()`.
+const DEBUGREG_OFFSET: usize = 848;
+
 fn align_addr_to_word(addr: usize) -> usize {
     addr & (-(size_of::<usize>() as isize) as usize)
 }
 
+/// Reads `size` little-endian bytes at `addr` from the inferior and returns
+/// them as a zero-extended `u64`.
+fn read_value(pid: Pid, addr: usize, size: usize) -> Result<u64, nix::Error> {
+    let word = ptrace::read(pid, align_addr_to_word(addr) as ptrace::AddressType)? as u64;
+    let byte_offset = addr - align_addr_to_word(addr);
+    let shifted = word >> (8 * byte_offset);
+    if size >= size_of::<usize>() {
+        Ok(shifted)
+    } else {
+        Ok(shifted & ((1u64 << (8 * size)) - 1))
+    }
+}
+
 impl Inferior {
     fn write_byte(pid: Pid, addr: usize, val: u8) -> Result<u8, nix::Error> {
         let aligned_addr = align_addr_to_word(addr);
         let byte_offset = addr - aligned_addr;
         let word = ptrace::read(pid, aligned_addr as ptrace::AddressType)? as u64;
-        let orig_byte = (word >> 8 * byte_offset) & 0xff;
-        let masked_word = word & !(0xff << 8 * byte_offset);
-        let updated_word = masked_word | ((val as u64) << 8 * byte_offset);
+        let orig_byte = (word >> (8 * byte_offset)) & 0xff;
+        let masked_word = word & !(0xff << (8 * byte_offset));
+        let updated_word = masked_word | ((val as u64) << (8 * byte_offset));
         unsafe {
             ptrace::write(
                 pid,
@@ -57,16 +99,150 @@ impl Inferior {
         }
         Ok(orig_byte as u8)
     }
+
+    /// Writes `data` into the userland register area of the traced process via
+    /// `PTRACE_POKEUSER`. `nix::sys::ptrace` exposes no wrapper for this
+    /// request, so we call `libc::ptrace` directly.
+    fn poke_user(pid: Pid, offset: usize, data: u64) -> Result<(), nix::Error> {
+        let res = unsafe {
+            libc::ptrace(
+                libc::PTRACE_POKEUSER,
+                pid.as_raw(),
+                offset as *mut libc::c_void,
+                data as *mut libc::c_void,
+            )
+        };
+        if res == -1 {
+            Err(nix::Error::last())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads a word from the userland register area of the traced process via
+    /// `PTRACE_PEEKUSER`. Like the `-1` return of `ptrace(2)` is ambiguous with
+    /// a legitimate `0xff..ff`, we clear `errno` first and consult it on `-1`.
+    fn peek_user(pid: Pid, offset: usize) -> Result<u64, nix::Error> {
+        unsafe { *libc::__errno_location() = 0 };
+        let res = unsafe {
+            libc::ptrace(
+                libc::PTRACE_PEEKUSER,
+                pid.as_raw(),
+                offset as *mut libc::c_void,
+                std::ptr::null_mut::<libc::c_void>(),
+            )
+        };
+        if res == -1 && nix::Error::last() != nix::Error::UnknownErrno {
+            Err(nix::Error::last())
+        } else {
+            Ok(res as u64)
+        }
+    }
+
+    /// Picks the x86 DR7 length encoding to use for a watchpoint that needs
+    /// to cover `size` bytes starting at `addr`. The DRn linear address must
+    /// be aligned to the encoded length (1, 2, 4 or 8 bytes), so we can't
+    /// just round `size` up to the next supported length: if `addr` isn't
+    /// aligned to that length the register would silently never trap. We
+    /// widen to the smallest supported length that is both `>= size` and a
+    /// divisor of `addr`. If no supported length fits (`size` exceeds 8, or
+    /// `addr` is not aligned to any length `>= size`), the full 8-byte length
+    /// is returned; `addr` is not realigned in that case.
+    fn watch_len_and_encoding(addr: usize, size: usize) -> (usize, u64) {
+        for &len in &[1usize, 2, 4, 8] {
+            if len >= size && addr.is_multiple_of(len) {
+                // Length encoding in DR7: 1->00, 2->01, 8->10, 4->11.
+                let bits = match len {
+                    1 => 0b00,
+                    2 => 0b01,
+                    8 => 0b10,
+                    _ => 0b11,
+                };
+                return (len, bits);
+            }
+        }
+        (8, 0b10)
+    }
+
+    /// Installs a hardware data watchpoint that stops the inferior when the
+    /// location at `addr` is written. `size` is the byte width of the value
+    /// being watched (e.g. the watched variable's type); the actual watched
+    /// length may be widened past `size` to satisfy the DRn alignment
+    /// requirement (see [`Inferior::watch_len_and_encoding`]). Up to four
+    /// watchpoints may be active at once (one per debug-address register).
+    /// Returns the index of the watchpoint, or `None` if all four slots are
+    /// already in use or a debug register or the watched memory could not be
+    /// accessed.
+    pub fn set_watchpoint(&mut self, addr: usize, size: usize) -> Option<usize> {
+        if self.watchpoints.len() >= DR_COUNT {
+            return None;
+        }
+        let n = self.watchpoints.len();
+        let pid = self.pid();
+        let (len, len_bits) = Inferior::watch_len_and_encoding(addr, size);
+        // DR0–DR3 hold the watched linear address.
+        Inferior::poke_user(pid, DEBUGREG_OFFSET + n * size_of::<usize>(), addr as u64).ok()?;
+        let dr7_offset = DEBUGREG_OFFSET + 7 * size_of::<usize>();
+        let mut dr7 = Inferior::peek_user(pid, dr7_offset).ok()?;
+        dr7 |= 1 << (2 * n); // local-enable bit for this watchpoint
+        dr7 |= 0b01 << (16 + 4 * n); // break on write
+        dr7 |= len_bits << (18 + 4 * n); // watched length
+        Inferior::poke_user(pid, dr7_offset, dr7).ok()?;
+        let value = read_value(pid, addr, len).ok()?;
+        self.watchpoints.push(Watchpoint { addr, size: len, value });
+        Some(n)
+    }
+
+    /// Reads DR6 after a SIGTRAP to determine which watchpoints fired. DR6
+    /// can have more than one B-bit set at once (e.g. two watched writes
+    /// retiring in the same step), so every set bit is collected before DR6
+    /// is cleared; clearing after inspecting only the first hit would
+    /// silently drop the others. Returns each fired watchpoint's index
+    /// together with its old and new values, refreshing the cached value for
+    /// each.
+    pub fn check_watchpoints(&mut self) -> Result<Vec<(usize, u64, u64)>, nix::Error> {
+        let pid = self.pid();
+        let dr6 = Inferior::peek_user(pid, DEBUGREG_OFFSET + 6 * size_of::<usize>())?;
+        let mut hits = Vec::new();
+        for n in 0..self.watchpoints.len() {
+            if dr6 & (1 << n) != 0 {
+                let (addr, size, old) = {
+                    let wp = &self.watchpoints[n];
+                    (wp.addr, wp.size, wp.value)
+                };
+                let new = read_value(pid, addr, size)?;
+                self.watchpoints[n].value = new;
+                hits.push((n, old, new));
+            }
+        }
+        if !hits.is_empty() {
+            // Clear DR6 only once every hit has been read, so the next hit on
+            // any register is observable.
+            Inferior::poke_user(pid, DEBUGREG_OFFSET + 6 * size_of::<usize>(), 0)?;
+        }
+        Ok(hits)
+    }
+
     /// Attempts to start a new inferior process. Returns Some(Inferior) if successful, or None if
     /// an error is encountered.
-    pub fn new(target: &str, args: &Vec<String>, breakpoints: &Vec<usize>) -> Option<Inferior> {
+    pub fn new(target: &str, config: &RunConfig, breakpoints: &[usize]) -> Option<Inferior> {
         let mut cmd = Command::new(target);
-        cmd.args(args);
+        cmd.args(&config.args);
+        cmd.envs(config.env.iter().map(|(k, v)| (k, v)));
+        if let Some(path) = &config.stdin {
+            cmd.stdin(File::open(path).ok()?);
+        }
+        if let Some(path) = &config.stdout {
+            cmd.stdout(File::create(path).ok()?);
+        }
+        if let Some(path) = &config.stderr {
+            cmd.stderr(File::create(path).ok()?);
+        }
         unsafe {
             cmd.pre_exec(child_traceme);
         }
         let child = cmd.spawn().ok()?;
-        let mut inferior = Inferior { child, breakpoint_map: HashMap::new() };
+        let mut inferior = Inferior { child, breakpoint_map: HashMap::new(), watchpoints: Vec::new() };
         let stat = inferior.wait(None).ok()?;
         if let Status::Stopped(Signal::SIGTRAP, _) = stat {
             breakpoints.iter().for_each(|&x| {
@@ -96,7 +272,7 @@ impl Inferior {
         })
     }
 
-    pub fn install_breakpoints(&mut self, breakpoints: &Vec::<usize>) {
+    pub fn install_breakpoints(&mut self, breakpoints: &[usize]) {
         breakpoints.iter().for_each(|&x| {
             self.breakpoint_map.insert(x, Inferior::write_byte(self.pid(), x, 0xccu8).ok().unwrap());
         })
@@ -110,22 +286,118 @@ impl Inferior {
         // Then execute THIS instruction, stop, and write 0xcc INT instruction back to addr.
         if let Some((&addr, &byte)) = self.breakpoint_map.get_key_value(&((regs.rip - 1) as usize)) {
             Inferior::write_byte(self.pid(), addr, byte)?;
-            regs.rip = regs.rip - 1;
+            regs.rip -= 1;
             ptrace::setregs(self.pid(), regs)?;
 
             ptrace::step(self.pid(), None)?;
-            match self.wait(None) {
-                Ok(Status::Stopped(Signal::SIGTRAP, _)) => {
-                    Inferior::write_byte(self.pid(), addr, 0xccu8)?;
-                },
-                _ => {},
-                // no need to handle Exited since next cont is called.
+            // no need to handle Exited since next cont is called.
+            if let Ok(Status::Stopped(Signal::SIGTRAP, _)) = self.wait(None) {
+                Inferior::write_byte(self.pid(), addr, 0xccu8)?;
             }
         }
         ptrace::cont(self.pid(), None)?;
         self.wait(None)
     }
 
+    /// Single-steps the inferior by one instruction, transparently restoring
+    /// and re-arming any breakpoint the instruction pointer is currently parked
+    /// on (mirrors the rewind logic in `go`). This lets stepping work even
+    /// across user breakpoints.
+    fn single_step(&self) -> Result<Status, nix::Error> {
+        let mut regs = ptrace::getregs(self.pid())?;
+        if let Some((&addr, &byte)) = self.breakpoint_map.get_key_value(&((regs.rip - 1) as usize)) {
+            Inferior::write_byte(self.pid(), addr, byte)?;
+            regs.rip -= 1;
+            ptrace::setregs(self.pid(), regs)?;
+            ptrace::step(self.pid(), None)?;
+            let status = self.wait(None)?;
+            if let Status::Stopped(Signal::SIGTRAP, _) = status {
+                Inferior::write_byte(self.pid(), addr, 0xccu8)?;
+            }
+            return Ok(status);
+        }
+        ptrace::step(self.pid(), None)?;
+        self.wait(None)
+    }
+
+    /// Steps the inferior forward one source line, stepping *into* any call.
+    /// Instructions are single-stepped until `rip` maps to a known source line
+    /// whose number differs from where the step began; compiler-generated
+    /// addresses with no line mapping are skipped.
+    pub fn step_line(&self, debug_data: &DwarfData) -> Result<Status, nix::Error> {
+        let start_line = debug_data
+            .get_line_from_addr(ptrace::getregs(self.pid())?.rip as usize)
+            .map(|line| line.number);
+        loop {
+            match self.single_step()? {
+                Status::Stopped(Signal::SIGTRAP, rip) => {
+                    if let Some(line) = debug_data.get_line_from_addr(rip) {
+                        if Some(line.number) != start_line {
+                            return Ok(Status::Stopped(Signal::SIGTRAP, rip));
+                        }
+                    }
+                }
+                other => return Ok(other),
+            }
+        }
+    }
+
+    /// Steps the inferior forward one source line, stepping *over* calls. A call
+    /// pushes the return address and so lowers `rsp` below its value at the
+    /// step's start; while `rsp` sits below that boundary we are inside a deeper
+    /// frame and keep stepping, only considering the line once execution has
+    /// returned to the original frame.
+    ///
+    /// This is a heuristic, not a precise call/return detector: it has no
+    /// disassembler, so it cannot tell a genuine `call` apart from any other
+    /// instruction that transiently moves `rsp` below `start_rsp` (a `push`,
+    /// a register spill, a stack `alloca`). Such an instruction on the very
+    /// next line will itself be treated as "still inside a call" and skipped
+    /// over rather than stopped at. The robust fix is to set a temporary
+    /// breakpoint at the return address pushed by a detected `call` instead
+    /// of polling `rsp`; that requires decoding instructions, which this
+    /// debugger doesn't currently do.
+    pub fn next_line(&self, debug_data: &DwarfData) -> Result<Status, nix::Error> {
+        let regs = ptrace::getregs(self.pid())?;
+        let start_rsp = regs.rsp as usize;
+        let start_line = debug_data.get_line_from_addr(regs.rip as usize).map(|line| line.number);
+        loop {
+            match self.single_step()? {
+                Status::Stopped(Signal::SIGTRAP, rip) => {
+                    // Still inside a call we stepped into: keep going until we
+                    // unwind back to the starting frame boundary.
+                    if (ptrace::getregs(self.pid())?.rsp as usize) < start_rsp {
+                        continue;
+                    }
+                    if let Some(line) = debug_data.get_line_from_addr(rip) {
+                        if Some(line.number) != start_line {
+                            return Ok(Status::Stopped(Signal::SIGTRAP, rip));
+                        }
+                    }
+                }
+                other => return Ok(other),
+            }
+        }
+    }
+
+    /// Returns the current frame base (`rbp`) and instruction pointer (`rip`),
+    /// used to resolve local variables when evaluating `print` expressions.
+    pub fn frame(&self) -> Result<(usize, usize), nix::Error> {
+        let regs = ptrace::getregs(self.pid())?;
+        Ok((regs.rbp as usize, regs.rip as usize))
+    }
+
+    /// Reads a full machine word from the inferior's address space.
+    pub fn read_word(&self, addr: usize) -> Result<usize, nix::Error> {
+        Ok(ptrace::read(self.pid(), addr as ptrace::AddressType)? as usize)
+    }
+
+    /// Reads `size` (1–8) bytes from the inferior at `addr`, zero-extended into
+    /// a `u64`. Used by the `print` evaluator to fetch scalar values.
+    pub fn read_bytes(&self, addr: usize, size: usize) -> Result<u64, nix::Error> {
+        read_value(self.pid(), addr, size)
+    }
+
     pub fn kill(&mut self) -> Result<Status, nix::Error> {
         if let Err(err) = self.child.kill() {
             println!("command cannot be killed.");
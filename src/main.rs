@@ -3,6 +3,8 @@ mod debugger_command;
 mod inferior;
 mod dwarf_data;
 mod gimli_wrapper;
+mod syntax;
+mod completer;
 use crate::debugger::Debugger;
 use nix::sys::signal::{signal, SigHandler, Signal};
 use std::env;
@@ -14,7 +16,14 @@ fn main() {
         std::process::exit(1);
     }
     let target = &args[1];
-    let print_info = if &args[2] == "-i" { true } else { println!("Unknown option {}", &args[2]); false };
+    let print_info = match args.get(2) {
+        None => false,
+        Some(opt) if opt == "-i" => true,
+        Some(opt) => {
+            println!("Unknown option {}", opt);
+            false
+        }
+    };
 
     // Disable handling of ctrl+c in this process (so that ctrl+c only gets delivered to child
     // processes)
@@ -0,0 +1,305 @@
+//! A tiny recursive-descent parser and evaluator for `print` expressions.
+//!
+//! The grammar is deliberately small — just enough to inspect a local
+//! variable and reach into it:
+//!
+//! ```text
+//! expr    := '*' expr           // dereference
+//!          | postfix
+//! postfix := primary ( '.' ident        // field access
+//!                     | '[' number ']'  // array index
+//!                     )*
+//! primary := ident
+//! ```
+//!
+//! The leading identifier is resolved against `DwarfData`: its `DW_AT_location`
+//! in the function covering the current `rip` gives a frame offset relative
+//! to the CFA frame base, which sits `RBP_TO_CFA` bytes above `rbp`. From
+//! there the variable's type DIE drives both how we walk `.field`/`[i]`/`*`
+//! and how we format the final bytes.
+
+use crate::dwarf_data::{DwarfData, Type};
+use crate::inferior::Inferior;
+
+/// Offset from `rbp` to the CFA frame base that `DW_OP_fbreg` offsets are
+/// relative to, for a standard `push rbp; mov rbp, rsp` prologue: 8 bytes for
+/// the pushed return address plus 8 for the pushed `rbp` itself.
+const RBP_TO_CFA: isize = 16;
+
+/// A parsed print expression.
+enum Expr {
+    /// A bare variable name, e.g. `x`.
+    Ident(String),
+    /// Member access, e.g. `obj.field`.
+    Field(Box<Expr>, String),
+    /// Array indexing, e.g. `arr[3]`.
+    Index(Box<Expr>, usize),
+    /// Pointer dereference, e.g. `*ptr`.
+    Deref(Box<Expr>),
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    _src: &'a str,
+}
+
+#[derive(PartialEq)]
+enum Token {
+    Ident(String),
+    Number(usize),
+    Dot,
+    LBracket,
+    RBracket,
+    Star,
+}
+
+/// Splits an expression into tokens. Returns `None` on an unexpected character.
+fn tokenize(src: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '.' => {
+                chars.next();
+                tokens.push(Token::Dot);
+            }
+            '[' => {
+                chars.next();
+                tokens.push(Token::LBracket);
+            }
+            ']' => {
+                chars.next();
+                tokens.push(Token::RBracket);
+            }
+            '*' => {
+                chars.next();
+                tokens.push(Token::Star);
+            }
+            c if c.is_ascii_digit() => {
+                let mut n = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() {
+                        n.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Number(n.parse().ok()?));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+impl<'a> Parser<'a> {
+    fn parse(src: &'a str) -> Option<Expr> {
+        let tokens = tokenize(src)?;
+        let mut parser = Parser { tokens, pos: 0, _src: src };
+        let expr = parser.expr()?;
+        if parser.pos == parser.tokens.len() {
+            Some(expr)
+        } else {
+            None
+        }
+    }
+
+    fn expr(&mut self) -> Option<Expr> {
+        if self.tokens.get(self.pos) == Some(&Token::Star) {
+            self.pos += 1;
+            return Some(Expr::Deref(Box::new(self.expr()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Option<Expr> {
+        let mut base = self.primary()?;
+        loop {
+            match self.tokens.get(self.pos) {
+                Some(Token::Dot) => {
+                    self.pos += 1;
+                    match self.tokens.get(self.pos) {
+                        Some(Token::Ident(field)) => {
+                            base = Expr::Field(Box::new(base), field.clone());
+                            self.pos += 1;
+                        }
+                        _ => return None,
+                    }
+                }
+                Some(Token::LBracket) => {
+                    self.pos += 1;
+                    let index = match self.tokens.get(self.pos) {
+                        Some(Token::Number(n)) => *n,
+                        _ => return None,
+                    };
+                    self.pos += 1;
+                    if self.tokens.get(self.pos) != Some(&Token::RBracket) {
+                        return None;
+                    }
+                    self.pos += 1;
+                    base = Expr::Index(Box::new(base), index);
+                }
+                _ => break,
+            }
+        }
+        Some(base)
+    }
+
+    fn primary(&mut self) -> Option<Expr> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Some(Expr::Ident(name))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An intermediate evaluation result: the inferior address holding the value
+/// together with its DWARF type.
+struct Typed {
+    addr: usize,
+    var_type: Type,
+}
+
+/// Evaluates a `print` expression against the stopped inferior and returns a
+/// formatted value. `rbp`/`rip` are the frame base and instruction pointer at
+/// the current stop.
+pub fn evaluate(
+    src: &str,
+    inferior: &Inferior,
+    debug_data: &DwarfData,
+    rbp: usize,
+    rip: usize,
+) -> Result<String, String> {
+    let expr = Parser::parse(src).ok_or_else(|| format!("Could not parse expression `{}`", src))?;
+    let typed = eval(&expr, inferior, debug_data, rbp, rip)?;
+    format_value(&typed, inferior)
+}
+
+fn eval(
+    expr: &Expr,
+    inferior: &Inferior,
+    debug_data: &DwarfData,
+    rbp: usize,
+    rip: usize,
+) -> Result<Typed, String> {
+    match expr {
+        Expr::Ident(name) => {
+            let func = debug_data
+                .get_function_from_addr(rip)
+                .ok_or_else(|| String::from("Not stopped in a known function"))?;
+            let var = debug_data
+                .get_variable_in_function(&func, name)
+                .ok_or_else(|| format!("No variable `{}` in scope", name))?;
+            Ok(Typed {
+                addr: (rbp as isize + RBP_TO_CFA + var.offset) as usize,
+                var_type: var.var_type,
+            })
+        }
+        Expr::Field(base, field) => {
+            let base = eval(base, inferior, debug_data, rbp, rip)?;
+            match base.var_type {
+                Type::Struct { members, .. } => {
+                    let member = members
+                        .into_iter()
+                        .find(|m| &m.name == field)
+                        .ok_or_else(|| format!("No member `{}`", field))?;
+                    Ok(Typed {
+                        addr: base.addr + member.offset,
+                        var_type: member.member_type,
+                    })
+                }
+                _ => Err(format!("`.{}` applied to a non-struct value", field)),
+            }
+        }
+        Expr::Index(base, index) => {
+            let base = eval(base, inferior, debug_data, rbp, rip)?;
+            match base.var_type {
+                Type::Array { element, stride, .. } => Ok(Typed {
+                    addr: base.addr + index * stride,
+                    var_type: *element,
+                }),
+                _ => Err(String::from("`[..]` applied to a non-array value")),
+            }
+        }
+        Expr::Deref(base) => {
+            let base = eval(base, inferior, debug_data, rbp, rip)?;
+            match base.var_type {
+                Type::Pointer { target } => {
+                    let addr = inferior
+                        .read_word(base.addr)
+                        .map_err(|e| format!("Could not read pointer: {}", e))?;
+                    Ok(Typed { addr, var_type: *target })
+                }
+                _ => Err(String::from("`*` applied to a non-pointer value")),
+            }
+        }
+    }
+}
+
+/// Formats the value at `typed.addr` according to its DWARF type, recursing
+/// into struct members.
+fn format_value(typed: &Typed, inferior: &Inferior) -> Result<String, String> {
+    match &typed.var_type {
+        Type::Base { size, signed, .. } => {
+            let raw = inferior
+                .read_bytes(typed.addr, *size)
+                .map_err(|e| format!("Could not read memory: {}", e))?;
+            if *signed {
+                // Sign-extend from `size` bytes.
+                let shift = 64 - 8 * *size;
+                Ok(format!("{}", ((raw << shift) as i64) >> shift))
+            } else {
+                Ok(format!("{}", raw))
+            }
+        }
+        Type::Pointer { .. } => {
+            let raw = inferior
+                .read_word(typed.addr)
+                .map_err(|e| format!("Could not read memory: {}", e))?;
+            Ok(format!("{:#x}", raw))
+        }
+        Type::Struct { name, members, .. } => {
+            let mut parts = Vec::new();
+            for member in members {
+                let inner = Typed {
+                    addr: typed.addr + member.offset,
+                    var_type: member.member_type.clone(),
+                };
+                parts.push(format!("{}: {}", member.name, format_value(&inner, inferior)?));
+            }
+            Ok(format!("{} {{ {} }}", name, parts.join(", ")))
+        }
+        Type::Array { element, stride, count } => {
+            let mut parts = Vec::new();
+            for i in 0..*count {
+                let inner = Typed {
+                    addr: typed.addr + i * stride,
+                    var_type: (**element).clone(),
+                };
+                parts.push(format_value(&inner, inferior)?);
+            }
+            Ok(format!("[{}]", parts.join(", ")))
+        }
+    }
+}
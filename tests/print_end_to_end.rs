@@ -0,0 +1,86 @@
+//! End-to-end regression test for `print` on a bare scalar local, a whole
+//! struct, and a whole array. Compiles a small C fixture with `-g`,
+//! drives the real `deet` binary against it over stdin, and checks the
+//! printed values. This is the scenario that caught the DWARF traversal bugs
+//! where `get_variable_in_function` and `resolve_type_die` broke out of
+//! their child-walking loop on the very first entry (the DIE they started
+//! from), leaving locals unresolved and structs/arrays empty.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const FIXTURE_SRC: &str = r#"
+struct Point { int x; int y; };
+
+int compute(int n) {
+    struct Point p = { n, n + 1 };
+    int arr[4] = {10, 20, 30, 40};
+    int result = n + arr[0] + p.x;
+    return result;
+}
+
+int main() {
+    int result = compute(5);
+    return result;
+}
+"#;
+
+/// Compiles `FIXTURE_SRC` with debug info and returns the path to the
+/// resulting binary (inside a fresh temp directory that outlives the test).
+fn build_fixture() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("deet_print_fixture_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create fixture dir");
+    let src_path = dir.join("fixture.c");
+    std::fs::write(&src_path, FIXTURE_SRC).expect("write fixture source");
+    let bin_path = dir.join("fixture");
+    // `deet` doesn't account for a PIE load bias, so the fixture must be
+    // linked as a fixed (non-PIE) executable for its DWARF addresses to line
+    // up with the addresses it actually runs at.
+    let status = Command::new("cc")
+        .args(["-g", "-O0", "-no-pie", "-o"])
+        .arg(&bin_path)
+        .arg(&src_path)
+        .status()
+        .expect("run cc");
+    assert!(status.success(), "fixture failed to compile");
+    bin_path
+}
+
+/// Runs the `deet` binary against `target`, feeding it `commands` on stdin
+/// (one per line) and returning everything written to stdout.
+fn run_deet(target: &std::path::Path, commands: &[&str]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_deet"))
+        .arg(target)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn deet");
+    let mut stdin = child.stdin.take().expect("deet stdin");
+    for command in commands {
+        writeln!(stdin, "{}", command).expect("write command");
+    }
+    drop(stdin);
+    let output = child.wait_with_output().expect("wait for deet");
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn print_local_struct_and_array() {
+    let target = build_fixture();
+    // Break just after `p` and `arr` are initialized but before `result` is
+    // computed, so all three locals are live.
+    let output = run_deet(&target, &["break 7", "run", "print n", "print p", "print arr", "quit"]);
+
+    assert!(output.contains("n = 5"), "bare local `n` not printed correctly:\n{}", output);
+    assert!(
+        output.contains("p = Point { x: 5, y: 6 }"),
+        "struct `p` not printed correctly:\n{}",
+        output
+    );
+    assert!(
+        output.contains("arr = [10, 20, 30, 40]"),
+        "array `arr` not printed correctly:\n{}",
+        output
+    );
+}